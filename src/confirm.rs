@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use alloy::network::Ethereum;
+use alloy::providers::PendingTransactionBuilder;
+use futures::future::join_all;
+
+/// How long to wait for a sent tx's receipt before counting it as dropped.
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Inclusion stats for one send tick.
+pub struct TickStats {
+    pub included: usize,
+    pub dropped: usize,
+    pub avg_inclusion_blocks: f64,
+}
+
+/// Awaits every tx sent this tick concurrently and summarizes inclusion.
+pub async fn await_tick(sent: Vec<(PendingTransactionBuilder<Ethereum>, u64)>) -> TickStats {
+    let total = sent.len();
+    let inclusion_blocks: Vec<u64> = join_all(sent.into_iter().map(|(pending, sent_block)| async move {
+        match pending.with_timeout(Some(RECEIPT_TIMEOUT)).get_receipt().await {
+            Ok(receipt) => Some(
+                receipt
+                    .block_number
+                    .unwrap_or(sent_block)
+                    .saturating_sub(sent_block),
+            ),
+            Err(err) => {
+                eprintln!("tx dropped, replaced, or timed out: {err}");
+                None
+            }
+        }
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let included = inclusion_blocks.len();
+    let avg_inclusion_blocks = if included > 0 {
+        inclusion_blocks.iter().sum::<u64>() as f64 / included as f64
+    } else {
+        0.0
+    };
+
+    TickStats {
+        included,
+        dropped: total - included,
+        avg_inclusion_blocks,
+    }
+}
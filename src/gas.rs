@@ -0,0 +1,51 @@
+use alloy::eips::eip4844::calc_blob_gasprice;
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::Provider;
+use eyre::{eyre, Result};
+
+use crate::GWEI;
+
+/// Fee parameters for one EIP-4844 transaction, derived from current network
+/// conditions rather than a hardcoded constant.
+pub struct Fees {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_blob_gas: u128,
+}
+
+/// Pulls the latest block's base fee and blob base fee from `provider` and scales
+/// them by `multiplier`, adding `priority_fee` as the tip. `ceiling`, if set,
+/// caps both `max_fee_per_gas` and `max_fee_per_blob_gas` so a fee spike can't
+/// blow the budget.
+pub async fn estimate_fees(
+    provider: &impl Provider,
+    multiplier: f64,
+    priority_fee: u128,
+    ceiling: Option<u128>,
+) -> Result<Fees> {
+    let latest = provider
+        .get_block_by_number(BlockNumberOrTag::Latest, false)
+        .await?
+        .ok_or_else(|| eyre!("provider returned no latest block"))?;
+    let base_fee = latest.header.base_fee_per_gas.unwrap_or_default() as u128;
+    let excess_blob_gas = latest.header.excess_blob_gas.unwrap_or_default();
+    let blob_base_fee = calc_blob_gasprice(excess_blob_gas);
+
+    let scale = |fee: u128| -> u128 {
+        let scaled = (fee as f64 * multiplier) as u128 + priority_fee;
+        match ceiling {
+            Some(ceiling) => scaled.min(ceiling),
+            None => scaled,
+        }
+    };
+
+    Ok(Fees {
+        max_fee_per_gas: scale(base_fee),
+        max_priority_fee_per_gas: priority_fee,
+        max_fee_per_blob_gas: scale(blob_base_fee),
+    })
+}
+
+pub fn gwei(value: u64) -> u128 {
+    value as u128 * GWEI
+}
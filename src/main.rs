@@ -1,4 +1,3 @@
-use alloy::consensus::{SidecarBuilder, SimpleCoder};
 use alloy::hex::FromHex;
 use alloy::network::{Ethereum, TransactionBuilder};
 use alloy::network::{EthereumWallet, NetworkWallet};
@@ -10,10 +9,21 @@ use clap::Parser;
 use eyre::{eyre, Result, WrapErr};
 use futures::future::join_all;
 use rand::prelude::*;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::{interval, MissedTickBehavior};
 use url::Url;
 
+mod confirm;
+mod gas;
+mod nonce;
+mod pool;
+mod sidecar;
+mod wallet;
+
+use nonce::NonceManager;
+use pool::ProviderPool;
+
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
@@ -25,6 +35,38 @@ struct Args {
     min: u8,
     #[arg(long, default_value_t = 3)]
     max: u8,
+    /// BIP-39 mnemonic phrase to derive child wallets from deterministically.
+    /// When omitted, child wallets are freshly randomized on every run.
+    #[arg(long)]
+    mnemonic: Option<String>,
+    /// Starting index into the `m/44'/60'/0'/0/{i}` derivation path, useful for
+    /// running multiple spammer instances off the same mnemonic.
+    #[arg(long, default_value_t = 0)]
+    account_offset: u32,
+    /// Multiplier applied to the observed base fee / blob base fee when
+    /// computing `max_fee_per_gas` / `max_fee_per_blob_gas` per tick.
+    #[arg(long, default_value_t = 2.0)]
+    fee_multiplier: f64,
+    /// Priority fee (gwei) added on top of the scaled base fee.
+    #[arg(long, default_value_t = 1)]
+    priority_fee_gwei: u64,
+    /// Upper bound (gwei) on `max_fee_per_gas` / `max_fee_per_blob_gas`, in
+    /// case of a fee spike. Unbounded if unset.
+    #[arg(long)]
+    fee_ceiling_gwei: Option<u64>,
+    /// Minimum number of 128 KiB blobs to attach per tx.
+    #[arg(long, default_value_t = 1)]
+    blobs_min: u8,
+    /// Maximum number of 128 KiB blobs to attach per tx (up to 6 under
+    /// EIP-4844).
+    #[arg(long, default_value_t = 1)]
+    blobs_max: u8,
+    /// How to pick among healthy RPCs in `--rpcs`.
+    #[arg(long, value_enum, default_value_t = pool::Strategy::WeightedRandom)]
+    provider_strategy: pool::Strategy,
+    /// How often (seconds) to re-probe down-weighted/unhealthy RPCs.
+    #[arg(long, default_value_t = 30)]
+    provider_probe_interval_secs: u64,
 }
 const GWEI: u128 = 1_000_000_000;
 
@@ -34,6 +76,15 @@ async fn main() -> Result<()> {
     if args.max < args.min || args.max == 0 {
         return Err(eyre!("inconsistent min & max"));
     }
+    if args.blobs_max < args.blobs_min || args.blobs_min == 0 || args.blobs_max > 6 {
+        return Err(eyre!("inconsistent blobs-min & blobs-max"));
+    }
+    if args
+        .fee_ceiling_gwei
+        .is_some_and(|ceiling| ceiling < args.priority_fee_gwei)
+    {
+        return Err(eyre!("fee-ceiling-gwei must be at least priority-fee-gwei"));
+    }
 
     let providers: Vec<_> = args
         .rpcs
@@ -48,9 +99,7 @@ async fn main() -> Result<()> {
         .wrap_err("while parsing private key")?,
     );
 
-    let child_wallets: Vec<_> = (0..args.max)
-        .map(|_| EthereumWallet::from(PrivateKeySigner::random()))
-        .collect();
+    let child_wallets = wallet::derive_child_wallets(&args, args.max)?;
 
     let provider = providers.first().unwrap();
     let balance = provider.get_balance(addr_of(&parent_wallet)).await?;
@@ -66,9 +115,6 @@ async fn main() -> Result<()> {
 
     let distribute_each = balance / U256::from(args.max + 1);
 
-    let sidecar: SidecarBuilder<SimpleCoder> = SidecarBuilder::from_slice(b"spam");
-    let sidecar = sidecar.build()?;
-
     let mut waiting = vec![];
     for wallet in &child_wallets {
         println!("funding {distribute_each}wei to {}", addr_of(wallet));
@@ -92,16 +138,86 @@ async fn main() -> Result<()> {
         .try_for_each(|e| e.map(|_| ()))?;
     println!("done funding");
 
+    let nonce_manager = NonceManager::new();
+
+    let provider_pool = Arc::new(ProviderPool::new(providers.len(), args.provider_strategy));
+    {
+        let providers = providers.clone();
+        let provider_pool = provider_pool.clone();
+        let probe_interval = Duration::from_secs(args.provider_probe_interval_secs);
+        tokio::spawn(async move {
+            let mut interval = interval(probe_interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                for (idx, provider) in providers.iter().enumerate() {
+                    match provider.get_chain_id().await {
+                        Ok(_) => provider_pool.restore(idx),
+                        Err(err) => {
+                            eprintln!("Error probing rpc {idx}: {err}");
+                            provider_pool.mark_failed_probe(idx);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     let mut interval = interval(Duration::from_secs(12));
     interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
     loop {
         interval.tick().await;
         let num = thread_rng().gen_range(args.min..=args.max);
         println!("sending {num} tx");
+        let tick_provider_idx = provider_pool.select(&mut thread_rng());
+        let tick_provider = &providers[tick_provider_idx];
+        let sent_block = match tick_provider.get_block_number().await {
+            Ok(n) => {
+                provider_pool.record_success(tick_provider_idx);
+                n
+            }
+            Err(err) => {
+                eprintln!("Error getting block number: {err}");
+                provider_pool.record_error(tick_provider_idx);
+                continue;
+            }
+        };
+        let fees = match gas::estimate_fees(
+            tick_provider,
+            args.fee_multiplier,
+            gas::gwei(args.priority_fee_gwei),
+            args.fee_ceiling_gwei.map(gas::gwei),
+        )
+        .await
+        {
+            Ok(fees) => {
+                provider_pool.record_success(tick_provider_idx);
+                fees
+            }
+            Err(err) => {
+                eprintln!("Error estimating fees: {err}");
+                provider_pool.record_error(tick_provider_idx);
+                continue;
+            }
+        };
+        let mut sent = vec![];
         for idx in 0..num {
-            let provider = providers.iter().choose(&mut thread_rng()).unwrap();
+            let provider_idx = provider_pool.select(&mut thread_rng());
+            let provider = &providers[provider_idx];
             let wallet = child_wallets.get(idx as usize).unwrap();
-            let nonce = match provider.get_transaction_count(addr_of(wallet)).await {
+            let addr = addr_of(wallet);
+            let sidecar = match sidecar::random_sidecar(
+                &mut thread_rng(),
+                args.blobs_min,
+                args.blobs_max,
+            ) {
+                Ok(sidecar) => sidecar,
+                Err(err) => {
+                    eprintln!("Error building blob sidecar: {err}");
+                    continue;
+                }
+            };
+            let nonce = match nonce_manager.next_nonce(provider, addr).await {
                 Ok(nonce) => nonce,
                 Err(err) => {
                     eprintln!("Error getting nonce: {err}");
@@ -109,25 +225,51 @@ async fn main() -> Result<()> {
                 }
             };
             let tx = TransactionRequest::default()
-                .with_to(addr_of(wallet))
+                .with_to(addr)
                 .with_nonce(nonce)
-                .with_max_fee_per_gas(10 * GWEI)
-                .with_max_fee_per_blob_gas(10 * GWEI)
-                .with_max_priority_fee_per_gas(GWEI)
+                .with_max_fee_per_gas(fees.max_fee_per_gas)
+                .with_max_fee_per_blob_gas(fees.max_fee_per_blob_gas)
+                .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
                 .with_chain_id(chain_id)
                 .with_value(U256::ZERO)
-                .with_from(addr_of(wallet))
+                .with_from(addr)
                 .with_gas_limit(21_000)
-                .with_blob_sidecar(sidecar.clone())
+                .with_blob_sidecar(sidecar)
                 .build(&wallet)
                 .await?;
-            if let Err(err) = provider.send_tx_envelope(tx).await {
-                eprintln!("Error sending tx: {err}");
+            match provider.send_tx_envelope(tx).await {
+                Ok(pending) => {
+                    provider_pool.record_success(provider_idx);
+                    sent.push((pending, sent_block));
+                }
+                Err(err) => {
+                    eprintln!("Error sending tx: {err}");
+                    provider_pool.record_error(provider_idx);
+                    if is_nonce_gap_error(&err) {
+                        if let Err(resync_err) = nonce_manager.resync(provider, addr).await {
+                            eprintln!("Error resyncing nonce: {resync_err}");
+                        }
+                    }
+                }
             }
         }
+        tokio::spawn(async move {
+            let stats = confirm::await_tick(sent).await;
+            println!(
+                "tick confirmed: {} included, {} dropped/replaced, avg inclusion {:.2} blocks",
+                stats.included, stats.dropped, stats.avg_inclusion_blocks
+            );
+        });
     }
 }
 
+/// Whether `err` looks like the node rejected the tx over a nonce mismatch,
+/// meaning our locally cached nonce has drifted from the chain.
+fn is_nonce_gap_error(err: &impl std::fmt::Display) -> bool {
+    let msg = err.to_string().to_lowercase();
+    msg.contains("nonce too low") || msg.contains("nonce too high") || msg.contains("invalid nonce")
+}
+
 fn addr_of(wallet: &EthereumWallet) -> Address {
     <EthereumWallet as NetworkWallet<Ethereum>>::default_signer_address(wallet)
 }
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use eyre::Result;
+
+/// Hands out per-address nonces without a chain round-trip on every send.
+///
+/// Each address's starting nonce is fetched once and cached in an `AtomicU64`;
+/// subsequent sends just `fetch_add`. Call [`NonceManager::resync`] after a
+/// send fails with a nonce-gap error to re-fetch that address's nonce from the
+/// chain.
+pub struct NonceManager {
+    next: Mutex<HashMap<Address, AtomicU64>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self {
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the next nonce to use for `addr`, fetching and caching the
+    /// current on-chain count the first time `addr` is seen.
+    pub async fn next_nonce(&self, provider: &impl Provider, addr: Address) -> Result<u64> {
+        if let Some(counter) = self.next.lock().unwrap().get(&addr) {
+            return Ok(counter.fetch_add(1, Ordering::SeqCst));
+        }
+
+        let current = provider.get_transaction_count(addr).await?;
+        let mut next = self.next.lock().unwrap();
+        let counter = next
+            .entry(addr)
+            .or_insert_with(|| AtomicU64::new(current));
+        Ok(counter.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Re-syncs `addr`'s cached nonce from the chain, discarding whatever was
+    /// handed out locally. Call this after a send fails with a nonce gap.
+    pub async fn resync(&self, provider: &impl Provider, addr: Address) -> Result<()> {
+        let current = provider.get_transaction_count(addr).await?;
+        self.next
+            .lock()
+            .unwrap()
+            .insert(addr, AtomicU64::new(current));
+        Ok(())
+    }
+}
+
+impl Default for NonceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use clap::ValueEnum;
+use rand::Rng;
+
+/// How the pool picks among healthy providers.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Strategy {
+    RoundRobin,
+    WeightedRandom,
+}
+
+/// Weight of the newest sample in the success-rate moving average.
+const EMA_ALPHA: f64 = 0.3;
+
+/// Exponential moving average of one endpoint's recent success rate.
+struct Health {
+    ema: Mutex<f64>,
+}
+
+impl Health {
+    fn new() -> Self {
+        Self {
+            ema: Mutex::new(1.0),
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let sample = if success { 1.0 } else { 0.0 };
+        let mut ema = self.ema.lock().unwrap();
+        *ema = EMA_ALPHA * sample + (1.0 - EMA_ALPHA) * *ema;
+    }
+
+    fn record_success(&self) {
+        self.record(true);
+    }
+
+    fn record_error(&self) {
+        self.record(false);
+    }
+
+    fn restore(&self) {
+        *self.ema.lock().unwrap() = 1.0;
+    }
+
+    /// Never fully zero, so a dead pool can still pick something.
+    fn weight(&self) -> f64 {
+        self.ema.lock().unwrap().max(0.01)
+    }
+
+    fn is_healthy(&self) -> bool {
+        *self.ema.lock().unwrap() > 0.5
+    }
+}
+
+/// Tracks per-endpoint health and selects which RPC to use next.
+pub struct ProviderPool {
+    health: Vec<Health>,
+    round_robin_cursor: AtomicUsize,
+    strategy: Strategy,
+}
+
+impl ProviderPool {
+    pub fn new(count: usize, strategy: Strategy) -> Self {
+        Self {
+            health: (0..count).map(|_| Health::new()).collect(),
+            round_robin_cursor: AtomicUsize::new(0),
+            strategy,
+        }
+    }
+
+    pub fn record_success(&self, idx: usize) {
+        self.health[idx].record_success();
+    }
+
+    pub fn record_error(&self, idx: usize) {
+        self.health[idx].record_error();
+    }
+
+    pub fn restore(&self, idx: usize) {
+        self.health[idx].restore();
+    }
+
+    pub fn mark_failed_probe(&self, idx: usize) {
+        self.health[idx].record_error();
+    }
+
+    /// Picks the next provider index, preferring healthy endpoints; falls
+    /// back to all endpoints if none are currently healthy.
+    pub fn select(&self, rng: &mut impl Rng) -> usize {
+        let healthy: Vec<usize> = (0..self.health.len())
+            .filter(|&i| self.health[i].is_healthy())
+            .collect();
+        let candidates = if healthy.is_empty() {
+            (0..self.health.len()).collect()
+        } else {
+            healthy
+        };
+
+        match self.strategy {
+            Strategy::RoundRobin => {
+                let step = self.round_robin_cursor.fetch_add(1, Ordering::Relaxed);
+                candidates[step % candidates.len()]
+            }
+            Strategy::WeightedRandom => {
+                let weights: Vec<f64> = candidates.iter().map(|&i| self.health[i].weight()).collect();
+                let total: f64 = weights.iter().sum();
+                let mut pick = rng.gen_range(0.0..total);
+                for (idx, weight) in candidates.iter().zip(weights.iter()) {
+                    if pick < *weight {
+                        return *idx;
+                    }
+                    pick -= weight;
+                }
+                *candidates.last().unwrap()
+            }
+        }
+    }
+}
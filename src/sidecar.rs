@@ -0,0 +1,55 @@
+use alloy::consensus::{BlobTransactionSidecar, SidecarBuilder, SimpleCoder};
+use eyre::{eyre, Result};
+use rand::Rng;
+
+/// `SimpleCoder` packs data into the 4096 field elements of a blob, using
+/// only 31 of each element's 32 bytes (the top byte is reserved padding), so
+/// this is the real amount of random payload one blob can hold.
+const BLOB_USABLE_BYTES: usize = 4096 * 31;
+
+/// Builds a sidecar carrying a random number of blobs (uniformly between
+/// `min` and `max`, inclusive) of full-size random data, so each blob tx
+/// commits a different amount of blob gas.
+pub fn random_sidecar(rng: &mut impl Rng, min: u8, max: u8) -> Result<BlobTransactionSidecar> {
+    let count = rng.gen_range(min..=max);
+    // SimpleCoder spends one field element (31 bytes) on a length header for
+    // each ingested payload, so leave room for it or the payload spills into an
+    // extra blob.
+    let mut payload = vec![0u8; BLOB_USABLE_BYTES * count as usize - 31];
+    rng.fill_bytes(&mut payload);
+
+    let mut builder: SidecarBuilder<SimpleCoder> = SidecarBuilder::default();
+    builder.ingest(&payload);
+    let sidecar = builder.build()?;
+
+    if sidecar.blobs.len() != count as usize {
+        return Err(eyre!(
+            "built sidecar with {} blobs, expected {count}",
+            sidecar.blobs.len()
+        ));
+    }
+    Ok(sidecar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_sidecar_matches_requested_blob_count() {
+        let mut rng = rand::thread_rng();
+        for count in 1..=6 {
+            let sidecar = random_sidecar(&mut rng, count, count).unwrap();
+            assert_eq!(sidecar.blobs.len(), count as usize);
+        }
+    }
+
+    #[test]
+    fn random_sidecar_stays_within_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let sidecar = random_sidecar(&mut rng, 2, 4).unwrap();
+            assert!((2..=4).contains(&sidecar.blobs.len()));
+        }
+    }
+}
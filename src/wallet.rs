@@ -0,0 +1,30 @@
+use alloy::network::EthereumWallet;
+use alloy::signers::local::coins_bip39::English;
+use alloy::signers::local::{MnemonicBuilder, PrivateKeySigner};
+use eyre::{Result, WrapErr};
+
+use crate::Args;
+
+/// Derives `count` child wallets from `args.mnemonic` via `m/44'/60'/0'/0/{account_offset + i}`,
+/// or falls back to random keys if no mnemonic is given.
+pub fn derive_child_wallets(args: &Args, count: u8) -> Result<Vec<EthereumWallet>> {
+    let Some(phrase) = &args.mnemonic else {
+        return Ok((0..count)
+            .map(|_| EthereumWallet::from(PrivateKeySigner::random()))
+            .collect());
+    };
+
+    (0..count)
+        .map(|idx| {
+            let account = args.account_offset + idx as u32;
+            let path = format!("m/44'/60'/0'/0/{account}");
+            let signer = MnemonicBuilder::<English>::default()
+                .phrase(phrase.as_str())
+                .derivation_path(&path)
+                .wrap_err("while setting mnemonic derivation path")?
+                .build()
+                .wrap_err("while deriving child wallet from mnemonic")?;
+            Ok(EthereumWallet::from(signer))
+        })
+        .collect()
+}